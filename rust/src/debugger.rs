@@ -0,0 +1,246 @@
+//! Interactive stepping debugger over the bytecode VM (see `step_vm`).
+//!
+//! Unlike `compile`, which lowers the *optimized* AST, `Debugger` compiles
+//! straight from `Token`s so every instruction keeps the source `Position`
+//! it came from, which is what makes breakpoints and "what line am I on"
+//! possible.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::{BFEvalError, Buffer, ByteIo, CellWrap, InvalidProgramError, OpCode, Position, Token, step_vm};
+
+/// Lowers a token stream to bytecode like `compile` does for `Expression`,
+/// but keeps a parallel `Vec<Position>` mapping each instruction back to the
+/// token it came from. Validates bracket balance the same way `parse` does,
+/// so an unbalanced program is rejected here rather than corrupting the
+/// bytecode it produces.
+fn compile_tokens(tokens: &[Token]) -> Result<(Vec<OpCode>, Vec<Position>), InvalidProgramError> {
+    let mut code = Vec::new();
+    let mut positions = Vec::new();
+    let mut loop_starts = Vec::new();
+
+    for token in tokens {
+        match token {
+            &Token::MoveForward(pos) => {
+                code.push(OpCode::Move(1));
+                positions.push(pos);
+            }
+            &Token::MoveBack(pos) => {
+                code.push(OpCode::Move(-1));
+                positions.push(pos);
+            }
+            &Token::IncValue(pos) => {
+                code.push(OpCode::Add(1));
+                positions.push(pos);
+            }
+            &Token::DecValue(pos) => {
+                code.push(OpCode::Add(-1));
+                positions.push(pos);
+            }
+            &Token::OutputValue(pos) => {
+                code.push(OpCode::Output);
+                positions.push(pos);
+            }
+            &Token::InputValue(pos) => {
+                code.push(OpCode::Input);
+                positions.push(pos);
+            }
+            &Token::LoopStart(pos) => {
+                loop_starts.push(code.len());
+                code.push(OpCode::JumpIfZero(0));
+                positions.push(pos);
+            }
+            &Token::LoopEnd(pos) => {
+                let start_idx = loop_starts.pop()
+                    .ok_or(InvalidProgramError::UnexpectedClosingBracket(pos))?;
+                let end_idx = code.len();
+                code.push(OpCode::JumpIfNonZero(start_idx + 1));
+                positions.push(pos);
+                code[start_idx] = OpCode::JumpIfZero(end_idx + 1);
+            }
+            &Token::ProgramStart | &Token::ProgramEnd => (),
+        }
+    }
+
+    if !loop_starts.is_empty() {
+        return Err(InvalidProgramError::ExcessiveOpeningBrackets(0));
+    }
+
+    Ok((code, positions))
+}
+
+/// A snapshot of VM state after a `step()`, for display by a caller.
+pub struct DebuggerState<'a> {
+    pub pointer: usize,
+    pub tape: &'a [u32],
+    pub position: Option<Position>,
+}
+
+/// Steps a compiled program one instruction at a time, stopping at
+/// breakpoints set by source `Position`. Generic over `ByteIo` so it runs
+/// the same way with or without `std`.
+pub struct Debugger<IO: ByteIo> {
+    code: Vec<OpCode>,
+    positions: Vec<Position>,
+    mem: Buffer<u32>,
+    ip: usize,
+    breakpoints: BTreeSet<Position>,
+    io: IO,
+}
+
+impl<IO: ByteIo> Debugger<IO> {
+    pub fn new(tokens: &[Token], io: IO) -> Result<Self, InvalidProgramError> {
+        let (code, positions) = compile_tokens(tokens)?;
+
+        Ok(Debugger {
+            code,
+            positions,
+            mem: Buffer::<u32>::new(30000, CellWrap::default()),
+            ip: 0,
+            breakpoints: BTreeSet::new(),
+            io,
+        })
+    }
+
+    pub fn add_breakpoint(&mut self, pos: Position) {
+        self.breakpoints.insert(pos);
+    }
+
+    pub fn remove_breakpoint(&mut self, pos: Position) {
+        self.breakpoints.remove(&pos);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.ip >= self.code.len()
+    }
+
+    /// The source position the next instruction (if any) came from.
+    pub fn position(&self) -> Option<Position> {
+        self.positions.get(self.ip).copied()
+    }
+
+    /// Executes exactly one instruction and returns the resulting state.
+    pub fn step(&mut self) -> Result<DebuggerState<'_>, BFEvalError> {
+        if !self.is_finished() {
+            step_vm(&self.code, &mut self.mem, &mut self.ip, &mut self.io)?;
+        }
+
+        Ok(self.state())
+    }
+
+    /// Steps until a breakpoint is hit or the program finishes.
+    pub fn run_to_breakpoint(&mut self) -> Result<DebuggerState<'_>, BFEvalError> {
+        while !self.is_finished() {
+            self.step()?;
+
+            if self.position().map_or(false, |pos| self.breakpoints.contains(&pos)) {
+                break;
+            }
+        }
+
+        Ok(self.state())
+    }
+
+    /// A window of `radius` cells on either side of the pointer, for display.
+    pub fn tape_window(&self, radius: usize) -> &[u32] {
+        let tape = self.mem.buf();
+        let ptr = self.mem.ptr();
+        let start = ptr.saturating_sub(radius);
+        let end = (ptr + radius + 1).min(tape.len());
+
+        &tape[start..end]
+    }
+
+    fn state(&self) -> DebuggerState<'_> {
+        DebuggerState {
+            pointer: self.mem.ptr(),
+            tape: self.mem.buf(),
+            position: self.position(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenize;
+
+    struct NullIo;
+
+    impl ByteIo for NullIo {
+        fn read(&mut self) -> Option<u8> {
+            None
+        }
+
+        fn write(&mut self, _b: u8) {}
+    }
+
+    #[test]
+    fn new_rejects_unexpected_closing_bracket() {
+        let tokens = tokenize(&"]".chars().collect());
+
+        assert!(matches!(
+            Debugger::new(&tokens, NullIo),
+            Err(InvalidProgramError::UnexpectedClosingBracket(0))
+        ));
+    }
+
+    #[test]
+    fn new_rejects_unclosed_opening_bracket() {
+        let tokens = tokenize(&"[+".chars().collect());
+
+        assert!(matches!(
+            Debugger::new(&tokens, NullIo),
+            Err(InvalidProgramError::ExcessiveOpeningBrackets(_))
+        ));
+    }
+
+    #[test]
+    fn step_advances_ip_and_applies_the_instruction() {
+        let tokens = tokenize(&"++".chars().collect());
+        let mut debugger = Debugger::new(&tokens, NullIo).unwrap();
+
+        let state = debugger.step().unwrap();
+        assert_eq!(state.tape[0], 1);
+        assert!(!debugger.is_finished());
+
+        let state = debugger.step().unwrap();
+        assert_eq!(state.tape[0], 2);
+        assert!(debugger.is_finished());
+    }
+
+    #[test]
+    fn run_to_breakpoint_stops_at_the_breakpoint_position() {
+        let tokens = tokenize(&"+++".chars().collect());
+        // The second `+` is token/position 1: execution stops just before it.
+        let mut debugger = Debugger::new(&tokens, NullIo).unwrap();
+        debugger.add_breakpoint(1);
+
+        let state = debugger.run_to_breakpoint().unwrap();
+
+        assert_eq!(state.tape[0], 1);
+        assert_eq!(debugger.position(), Some(1));
+        assert!(!debugger.is_finished());
+    }
+
+    #[test]
+    fn run_to_breakpoint_runs_to_completion_without_one() {
+        let tokens = tokenize(&"+++".chars().collect());
+        let mut debugger = Debugger::new(&tokens, NullIo).unwrap();
+
+        let state = debugger.run_to_breakpoint().unwrap();
+
+        assert_eq!(state.tape[0], 3);
+        assert!(debugger.is_finished());
+    }
+
+    #[test]
+    fn tape_window_is_centered_on_the_pointer() {
+        let tokens = tokenize(&">++".chars().collect());
+        let mut debugger = Debugger::new(&tokens, NullIo).unwrap();
+        debugger.run_to_breakpoint().unwrap();
+
+        assert_eq!(debugger.tape_window(1), &[0, 2, 0]);
+    }
+}
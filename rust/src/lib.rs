@@ -1,13 +1,20 @@
-use std;
-use std::ops::Add;
-use std::io::{self, prelude::*};
-use std::convert::TryInto;
+#![no_std]
 
-use std::ops::{AddAssign, SubAssign};
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::ops::Add;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
 
 extern crate num;
 use num::Zero;
 
+pub mod debugger;
+
 type Position = usize;
 
 #[derive(Debug)]
@@ -19,32 +26,132 @@ pub enum InvalidProgramError {
 #[derive(Debug)]
 pub enum BFEvalError {
     InvalidProgramError(InvalidProgramError),
-    IOError(std::io::Error),
 }
 
-impl std::convert::From<std::io::Error> for BFEvalError {
-    fn from(err: std::io::Error) -> BFEvalError {
-        BFEvalError::IOError(err)
+impl core::convert::From<InvalidProgramError> for BFEvalError {
+    fn from(err: InvalidProgramError) -> BFEvalError {
+        BFEvalError::InvalidProgramError(err)
     }
 }
 
-impl std::convert::From<InvalidProgramError> for BFEvalError {
-    fn from(err: InvalidProgramError) -> BFEvalError {
-        BFEvalError::InvalidProgramError(err)
+/// Byte-level I/O for `,`/`.`, abstracted so the interpreter can run without
+/// `std` (embedded, kernel, wasm hosts). `read` returns `None` on EOF/error;
+/// callers leave the current cell unchanged in that case.
+pub trait ByteIo {
+    fn read(&mut self) -> Option<u8>;
+    fn write(&mut self, b: u8);
+}
+
+/// The default `ByteIo` over `std::io::stdin`/`stdout`, available whenever
+/// the crate is built with its default `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct StdIo;
+
+#[cfg(feature = "std")]
+impl ByteIo for StdIo {
+    fn read(&mut self) -> Option<u8> {
+        use std::io::Read;
+
+        let mut input: [u8; 1] = [0];
+        match std::io::stdin().read(&mut input) {
+            Ok(1) => Some(input[0]),
+            _ => None,
+        }
+    }
+
+    fn write(&mut self, b: u8) {
+        use std::io::Write;
+
+        let _ = std::io::stdout().write_all(&[b]);
+        let _ = std::io::stdout().flush();
+    }
+}
+
+/// Free-function std I/O kept for the `rs` transpile target's generated
+/// code (see `translate::RustTarget`), which calls these directly instead of
+/// going through this crate's `ByteIo` abstraction.
+#[cfg(feature = "std")]
+pub fn read_mem() -> Result<u32, std::io::Error> {
+    use std::io::Read;
+
+    let mut input: [u8; 1] = [0];
+    std::io::stdin().read(&mut input)?;
+    Ok(input[0].into())
+}
+
+#[cfg(feature = "std")]
+pub fn print_mem(mem: u32) -> Result<(), std::io::Error> {
+    use std::io::Write;
+
+    std::io::stdout().write_all(&[mem as u8])?;
+    std::io::stdout().flush()
+}
+
+/// The cell width BF arithmetic wraps at, emulating fixed-width integer
+/// overflow/underflow. `Bits8` is the canonical Brainfuck cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellWrap {
+    Bits8,
+    Bits16,
+    Bits32,
+}
+
+impl CellWrap {
+    fn mask(self) -> u32 {
+        match self {
+            CellWrap::Bits8  => 0xFF,
+            CellWrap::Bits16 => 0xFFFF,
+            CellWrap::Bits32 => 0xFFFF_FFFF,
+        }
+    }
+}
+
+impl Default for CellWrap {
+    fn default() -> Self {
+        CellWrap::Bits8
+    }
+}
+
+/// A cell type that can wrap modulo a configurable `CellWrap` width instead
+/// of over/underflowing its native Rust width.
+pub trait WrappingCell: Zero + Copy {
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn masked(self, wrap: CellWrap) -> Self;
+}
+
+impl WrappingCell for u32 {
+    fn wrapping_add(self, other: Self) -> Self {
+        u32::wrapping_add(self, other)
+    }
+
+    fn wrapping_sub(self, other: Self) -> Self {
+        u32::wrapping_sub(self, other)
+    }
+
+    fn masked(self, wrap: CellWrap) -> Self {
+        self & wrap.mask()
     }
 }
 
+/// Tape cells are allocated in fixed blocks as the pointer advances past the
+/// end, so unbounded rightward-growing programs only pay for what they touch.
+const GROWTH_INCREMENT: usize = 32 * 1024;
+
 pub struct Buffer<T> {
     buf: Vec<T>,
     ptr: usize,
+    wrap: CellWrap,
 }
 
-impl<T> Buffer<T> 
-    where T: Zero + Copy + AddAssign + SubAssign {
-    pub fn new(buf_size: usize) -> Self {
+impl<T> Buffer<T>
+    where T: WrappingCell {
+    pub fn new(buf_size: usize, wrap: CellWrap) -> Self {
         let mut buffer = Self {
             buf: Vec::with_capacity(buf_size),
-            ptr: 0 
+            ptr: 0,
+            wrap,
         };
 
         for _ in 0..buf_size {
@@ -54,10 +161,11 @@ impl<T> Buffer<T>
         buffer
     }
 
-    pub fn clone(buf: &[T]) -> Self {
+    pub fn clone(buf: &[T], wrap: CellWrap) -> Self {
         let mut buffer = Self {
             buf: Vec::with_capacity(buf.len()),
-            ptr: 0 
+            ptr: 0,
+            wrap,
         };
 
         for i in 0..buf.len() {
@@ -71,20 +179,37 @@ impl<T> Buffer<T>
         &self.buf[..]
     }
 
+    pub fn ptr(&self) -> usize {
+        self.ptr
+    }
+
     pub fn fwd(&mut self, offset: usize) {
         self.ptr += offset;
+
+        while self.ptr >= self.buf.len() {
+            let new_len = self.buf.len() + GROWTH_INCREMENT;
+            self.buf.resize(new_len, T::zero());
+        }
     }
 
     pub fn bwd(&mut self, offset: usize) {
         self.ptr -= offset;
     }
 
+    pub fn move_by(&mut self, offset: isize) {
+        if offset >= 0 {
+            self.fwd(offset as usize);
+        } else {
+            self.bwd((-offset) as usize);
+        }
+    }
+
     pub fn inc(&mut self, offset: T) {
-        self.buf[self.ptr] += offset;
+        self.buf[self.ptr] = self.buf[self.ptr].wrapping_add(offset).masked(self.wrap);
     }
 
     pub fn dec(&mut self, offset: T) {
-        self.buf[self.ptr] -= offset;
+        self.buf[self.ptr] = self.buf[self.ptr].wrapping_sub(offset).masked(self.wrap);
     }
 
     pub fn read(&self) -> T {
@@ -92,22 +217,10 @@ impl<T> Buffer<T>
     }
 
     pub fn write(&mut self, val: T) {
-        self.buf[self.ptr] = val;
+        self.buf[self.ptr] = val.masked(self.wrap);
     }
 }
 
-pub fn read_mem() -> Result<u32, std::io::Error> {
-    let mut input: [u8; 1] = [0];
-    io::stdin().read(&mut input)?;
-    Ok(input[0].into())
-}
-
-pub fn print_mem(mem: u32) -> Result<(), std::io::Error> {
-    let x: u8 = mem.try_into().unwrap();
-    print!("{}", x as char);
-    io::stdout().flush()
-}
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Token {
     ProgramStart,
@@ -155,6 +268,10 @@ pub enum Expression {
     InputValue,
     OutputValue,
     Loop(Vec<Expression>),
+    // Constant-time idioms folded out of `Loop` by `optimize`.
+    SetValue(u32),
+    AddMultiple { offset: isize, factor: u32 },
+    Scan { step: isize },
 }
 
 impl Clone for Expression {
@@ -167,6 +284,10 @@ impl Clone for Expression {
             &Expression::InputValue     => Expression::InputValue,
             &Expression::OutputValue    => Expression::OutputValue,
              Expression::Loop(sub_exp)  => Expression::Loop(sub_exp.clone()),
+            &Expression::SetValue(n)    => Expression::SetValue(n),
+            &Expression::AddMultiple { offset, factor } =>
+                Expression::AddMultiple { offset, factor },
+            &Expression::Scan { step }  => Expression::Scan { step },
         }
     }
 }
@@ -177,8 +298,8 @@ pub fn parse(tokens: &Vec<Token>)
     Ok(expressions)
 }
 
-fn do_parse(mut tokens: std::slice::Iter<Token>, level: u32) 
-    -> Result<(Vec<Expression>, std::slice::Iter<Token>), InvalidProgramError>
+fn do_parse(mut tokens: core::slice::Iter<Token>, level: u32)
+    -> Result<(Vec<Expression>, core::slice::Iter<Token>), InvalidProgramError>
 {
     let mut expressions = Vec::new();
 
@@ -229,15 +350,75 @@ fn replace_top<T>(v: &mut Vec<T>, e: T) {
     v.push(e);
 }
 
+/// Recognizes classic constant-time Brainfuck loop idioms in an already
+/// RLE-optimized loop body and, if one matches, returns the expressions to
+/// splice in as a replacement for the whole `Loop`.
+fn fold_loop(sub_exp: &Vec<Expression>) -> Option<Vec<Expression>> {
+    match sub_exp.as_slice() {
+        // `[-]` / `[+]`: unconditionally zeroes the current cell.
+        [Expression::DecValue(1)] | [Expression::IncValue(1)] =>
+            return Some(vec![Expression::SetValue(0)]),
+
+        // `[>]` / `[<]`: advances to the next zero cell.
+        [Expression::MoveForward(n)] =>
+            return Some(vec![Expression::Scan { step: *n as isize }]),
+        [Expression::MoveBack(n)] =>
+            return Some(vec![Expression::Scan { step: -(*n as isize) }]),
+
+        _ => (),
+    }
+
+    // Multiply/copy loop, e.g. `[->++>+<<]`: walk the body tracking the
+    // pointer offset and the net delta applied at each offset visited. The
+    // loop is a multiply loop only if the pointer returns to where it
+    // started and the current cell is decremented by exactly one per
+    // iteration, with no I/O or nested loops along the way.
+    let mut offset: isize = 0;
+    let mut deltas: BTreeMap<isize, i64> = BTreeMap::new();
+
+    for expression in sub_exp {
+        match expression {
+            &Expression::IncValue(n)    => *deltas.entry(offset).or_insert(0) += n as i64,
+            &Expression::DecValue(n)    => *deltas.entry(offset).or_insert(0) -= n as i64,
+            &Expression::MoveForward(n) => offset += n as isize,
+            &Expression::MoveBack(n)    => offset -= n as isize,
+            _                           => return None,
+        }
+    }
+
+    if offset != 0 || deltas.get(&0) != Some(&-1) {
+        return None;
+    }
+
+    let mut folded = Vec::new();
+
+    for (&target_offset, &factor) in &deltas {
+        if target_offset == 0 {
+            continue;
+        }
+        if factor <= 0 {
+            return None;
+        }
+        folded.push(Expression::AddMultiple { offset: target_offset, factor: factor as u32 });
+    }
+
+    folded.push(Expression::SetValue(0));
+
+    Some(folded)
+}
+
 pub fn optimize(expressions: &Vec<Expression>) -> Vec<Expression> {
     let mut optimized: Vec<Expression> = Vec::new();
 
     for expression in expressions {
         match (optimized.last(), expression) {
             (_, Expression::Loop(sub_exp)) => {
-                optimized.push(
-                    Expression::Loop(optimize(sub_exp))
-                );
+                let optimized_sub = optimize(sub_exp);
+
+                match fold_loop(&optimized_sub) {
+                    Some(folded) => optimized.extend(folded),
+                    None         => optimized.push(Expression::Loop(optimized_sub)),
+                }
             },
 
             (Some(&Expression::IncValue(n)),    Expression::IncValue(1)) =>
@@ -260,33 +441,129 @@ pub fn optimize(expressions: &Vec<Expression>) -> Vec<Expression> {
     optimized
 }
 
-pub fn run(expressions: &Vec<Expression>) 
+/// A flat, jump-based instruction for the linear VM in `execute`.
+///
+/// `compile` lowers a `Vec<Expression>` tree into a `Vec<OpCode>` so that
+/// running a program no longer recurses into loop bodies: a `Loop` becomes a
+/// `JumpIfZero`/`JumpIfNonZero` pair bracketing its compiled body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpCode {
+    Add(i32),
+    Move(isize),
+    Output,
+    Input,
+    JumpIfZero(usize),
+    JumpIfNonZero(usize),
+    SetValue(u32),
+    AddMultiple { offset: isize, factor: u32 },
+    Scan { step: isize },
+}
+
+pub fn compile(expressions: &Vec<Expression>) -> Vec<OpCode> {
+    let mut code = Vec::new();
+    compile_into(expressions, &mut code);
+    code
+}
+
+fn compile_into(expressions: &Vec<Expression>, code: &mut Vec<OpCode>) {
+    for expression in expressions {
+        match expression {
+            &Expression::IncValue(n)    => code.push(OpCode::Add(n as i32)),
+            &Expression::DecValue(n)    => code.push(OpCode::Add(-(n as i32))),
+            &Expression::MoveForward(n) => code.push(OpCode::Move(n as isize)),
+            &Expression::MoveBack(n)    => code.push(OpCode::Move(-(n as isize))),
+             Expression::OutputValue    => code.push(OpCode::Output),
+             Expression::InputValue     => code.push(OpCode::Input),
+             Expression::Loop(sub_exp)  => {
+                // Push a placeholder `JumpIfZero` and back-patch its target
+                // once we know where the matching `JumpIfNonZero` lands.
+                let jump_if_zero_idx = code.len();
+                code.push(OpCode::JumpIfZero(0));
+
+                compile_into(sub_exp, code);
+
+                let jump_if_non_zero_idx = code.len();
+                code.push(OpCode::JumpIfNonZero(jump_if_zero_idx + 1));
+                code[jump_if_zero_idx] = OpCode::JumpIfZero(jump_if_non_zero_idx + 1);
+            }
+            &Expression::SetValue(n) => code.push(OpCode::SetValue(n)),
+            &Expression::AddMultiple { offset, factor } =>
+                code.push(OpCode::AddMultiple { offset, factor }),
+            &Expression::Scan { step } => code.push(OpCode::Scan { step }),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn run(expressions: &Vec<Expression>)
+    -> Result<Buffer<u32>, BFEvalError> {
+    run_with_wrap(expressions, CellWrap::default())
+}
+
+#[cfg(feature = "std")]
+pub fn run_with_wrap(expressions: &Vec<Expression>, wrap: CellWrap)
     -> Result<Buffer<u32>, BFEvalError> {
-    let mut mem = Buffer::<u32>::new(30000);
+    run_with_io(expressions, wrap, &mut StdIo)
+}
+
+pub fn run_with_io<IO: ByteIo>(expressions: &Vec<Expression>, wrap: CellWrap, io: &mut IO)
+    -> Result<Buffer<u32>, BFEvalError> {
+    let mut mem = Buffer::<u32>::new(30000, wrap);
+    let code = compile(expressions);
 
-    do_run(expressions, &mut mem)?;
+    execute(&code, &mut mem, io)?;
 
     Ok(mem)
 }
 
-fn do_run(expressions: &Vec<Expression>, mem: &mut Buffer<u32>)
+fn execute<IO: ByteIo>(code: &Vec<OpCode>, mem: &mut Buffer<u32>, io: &mut IO)
     -> Result<(), BFEvalError> {
-    for expression in expressions {
-        match expression {
-            &Expression::MoveForward(n) => mem.fwd(n),
-            &Expression::MoveBack(n)    => mem.bwd(n),
-            &Expression::IncValue(n)    => mem.inc(n),
-            &Expression::DecValue(n)    => mem.dec(n),
-             Expression::OutputValue    => print_mem(mem.read())?,
-             Expression::InputValue     => mem.write(read_mem()?),
-             Expression::Loop(sub_exp)  => {
-                while mem.read() > 0 {
-                    do_run(sub_exp, mem)?;
-                }
+    let mut ip = 0;
+
+    while ip < code.len() {
+        step_vm(code, mem, &mut ip, io)?;
+    }
+
+    Ok(())
+}
+
+/// Executes a single instruction at `*ip`, advancing it past the
+/// instruction (or to a jump target). Shared by the batch `execute` loop
+/// and `debugger::Debugger`, which drives it one instruction at a time.
+pub(crate) fn step_vm<IO: ByteIo>(code: &Vec<OpCode>, mem: &mut Buffer<u32>, ip: &mut usize, io: &mut IO)
+    -> Result<(), BFEvalError> {
+    match code[*ip] {
+        OpCode::Add(n) if n >= 0  => mem.inc(n as u32),
+        OpCode::Add(n)            => mem.dec((-n) as u32),
+        OpCode::Move(n)           => mem.move_by(n),
+        OpCode::Output            => io.write(mem.read() as u8),
+        OpCode::Input             => if let Some(b) = io.read() { mem.write(b as u32); },
+        OpCode::JumpIfZero(target) =>
+            if mem.read() == 0 {
+                *ip = target;
+                return Ok(());
+            },
+        OpCode::JumpIfNonZero(target) =>
+            if mem.read() > 0 {
+                *ip = target;
+                return Ok(());
+            },
+        OpCode::SetValue(n) => mem.write(n),
+        OpCode::AddMultiple { offset, factor } => {
+            let value = mem.read();
+            mem.move_by(offset);
+            mem.inc(value.wrapping_mul(factor));
+            mem.move_by(-offset);
+        }
+        OpCode::Scan { step } => {
+            while mem.read() > 0 {
+                mem.move_by(step);
             }
         }
     }
 
+    *ip += 1;
+
     Ok(())
 }
 
@@ -298,7 +575,8 @@ pub struct Stats {
     pub dec_count: usize,
     pub output_count: usize,
     pub input_count: usize,
-    pub loop_count: usize
+    pub loop_count: usize,
+    pub folded_count: usize
 }
 
 impl Add for Stats {
@@ -312,7 +590,8 @@ impl Add for Stats {
             dec_count: self.dec_count + other.dec_count,
             output_count: self.output_count + other.output_count,
             input_count: self.input_count + other.input_count,
-            loop_count: self.loop_count + other.loop_count
+            loop_count: self.loop_count + other.loop_count,
+            folded_count: self.folded_count + other.folded_count
         }
     }
 }
@@ -329,6 +608,10 @@ pub fn stats(expressions: &Vec<Expression>) -> Stats {
             Expression::OutputValue    => acc.output_count += 1,
             Expression::InputValue     => acc.input_count += 1,
             Expression::Loop(_)        => acc.loop_count += 1,
+            // Constant-time idioms folded out of a `Loop` by `optimize`.
+            Expression::SetValue(_)       |
+            Expression::AddMultiple { .. } |
+            Expression::Scan { .. }     => acc.folded_count += 1,
         }
     }
 
@@ -337,8 +620,157 @@ pub fn stats(expressions: &Vec<Expression>) -> Stats {
         .fold(
             acc,
             |acc, x| match x {
-                Expression::Loop(sub_exp) => acc + stats(sub_exp), 
+                Expression::Loop(sub_exp) => acc + stats(sub_exp),
                 _ => acc
             }
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestIo {
+        input: alloc::collections::VecDeque<u8>,
+        output: Vec<u8>,
+    }
+
+    impl TestIo {
+        fn new(input: &[u8]) -> Self {
+            TestIo { input: input.iter().copied().collect(), output: Vec::new() }
+        }
+    }
+
+    impl ByteIo for TestIo {
+        fn read(&mut self) -> Option<u8> {
+            self.input.pop_front()
+        }
+
+        fn write(&mut self, b: u8) {
+            self.output.push(b);
+        }
+    }
+
+    fn run(program: &str, input: &[u8]) -> (Buffer<u32>, Vec<u8>) {
+        let tokens = tokenize(&program.chars().collect());
+        let ast = parse(&tokens).expect("valid program");
+        let optimized = optimize(&ast);
+        let mut io = TestIo::new(input);
+        let mem = run_with_io(&optimized, CellWrap::default(), &mut io).expect("runs");
+
+        (mem, io.output)
+    }
+
+    #[test]
+    fn executes_hello_world() {
+        let (_, output) = run(
+            "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.",
+            &[],
+        );
+
+        assert_eq!(output, b"Hello World!\n");
+    }
+
+    #[test]
+    fn echoes_input() {
+        let (_, output) = run(",.,.", &[1, 2]);
+
+        assert_eq!(output, vec![1, 2]);
+    }
+
+    #[test]
+    fn optimize_folds_clear_loop() {
+        let tokens = tokenize(&"+++[-]".chars().collect());
+        let ast = parse(&tokens).unwrap();
+        let optimized = optimize(&ast);
+
+        assert_eq!(optimized, vec![Expression::IncValue(3), Expression::SetValue(0)]);
+    }
+
+    #[test]
+    fn optimize_folds_scan_loop() {
+        let tokens = tokenize(&"[>>]".chars().collect());
+        let ast = parse(&tokens).unwrap();
+        let optimized = optimize(&ast);
+
+        assert_eq!(optimized, vec![Expression::Scan { step: 2 }]);
+    }
+
+    #[test]
+    fn optimize_folds_multiply_loop() {
+        // `[->++>+++<<]`: adds cell 0 *2 into cell+1 and *3 into cell+2, clearing cell 0.
+        let tokens = tokenize(&"[->++>+++<<]".chars().collect());
+        let ast = parse(&tokens).unwrap();
+        let optimized = optimize(&ast);
+
+        assert_eq!(optimized, vec![
+            Expression::AddMultiple { offset: 1, factor: 2 },
+            Expression::AddMultiple { offset: 2, factor: 3 },
+            Expression::SetValue(0),
+        ]);
+    }
+
+    #[test]
+    fn optimize_leaves_non_multiply_loop_alone() {
+        // The pointer ends two cells right of where it started, so this isn't a multiply loop.
+        let tokens = tokenize(&"[->>]".chars().collect());
+        let ast = parse(&tokens).unwrap();
+        let optimized = optimize(&ast);
+
+        assert!(matches!(optimized.as_slice(), [Expression::Loop(_)]));
+    }
+
+    #[test]
+    fn add_multiple_wraps_instead_of_panicking_on_overflow() {
+        // Large per-iteration factor times a large cell value must wrap, not
+        // overflow-panic or diverge from what an un-optimized loop would do.
+        let mut mem = Buffer::<u32>::new(4, CellWrap::Bits32);
+        mem.write(u32::MAX);
+        let code = compile(&vec![Expression::AddMultiple { offset: 1, factor: u32::MAX }]);
+        let mut io = TestIo::new(&[]);
+
+        execute(&code, &mut mem, &mut io).expect("does not panic");
+
+        mem.move_by(1);
+        assert_eq!(mem.read(), u32::MAX.wrapping_mul(u32::MAX));
+    }
+
+    #[test]
+    fn cell_wrap_masks_to_configured_width() {
+        let mut mem = Buffer::<u32>::new(1, CellWrap::Bits8);
+        mem.inc(0xFF);
+        mem.inc(1);
+
+        assert_eq!(mem.read(), 0);
+    }
+
+    #[test]
+    fn buffer_grows_past_initial_capacity() {
+        let mut mem = Buffer::<u32>::new(1, CellWrap::default());
+
+        mem.fwd(GROWTH_INCREMENT * 2);
+        mem.inc(42);
+
+        assert_eq!(mem.read(), 42);
+    }
+
+    #[test]
+    fn parse_rejects_unexpected_closing_bracket() {
+        let tokens = tokenize(&"]".chars().collect());
+
+        assert!(matches!(
+            parse(&tokens),
+            Err(InvalidProgramError::UnexpectedClosingBracket(0))
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_unclosed_opening_bracket() {
+        let tokens = tokenize(&"[+".chars().collect());
+
+        assert!(matches!(
+            parse(&tokens),
+            Err(InvalidProgramError::ExcessiveOpeningBrackets(_))
+        ));
+    }
+}
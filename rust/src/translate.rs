@@ -1,35 +1,50 @@
 use std::io::{self, prelude::*};
 use std::env;
 
-mod bf;
-
 trait Target {
-    fn translate(token: &bf::Token) -> &'static str;
+    fn header() -> &'static str;
+    fn footer() -> &'static str;
+    fn translate(expr: &bf::Expression) -> String;
 }
 
 struct RustTarget;
 
 impl Target for RustTarget {
-    fn translate(token: &bf::Token) -> &'static str {
-        match token {
-            bf::Token::ProgramStart   => r#"
-                mod bf;
-
-                fn main() -> Result<(), std::io::Error> {
-                    let mut state = bf::BFState::new(30000);
-            "#,
-            bf::Token::MoveForward(_) => "state.fwd();",
-            bf::Token::MoveBack(_)    => "state.bwd();",
-            bf::Token::IncValue(_)    => "state.inc();",
-            bf::Token::DecValue(_)    => "state.dec();",
-            bf::Token::OutputValue(_) => "bf::print_mem(state.read())?;",
-            bf::Token::InputValue(_)  => "state.write(bf::read_mem()?);",
-            bf::Token::LoopStart(_)   => "while state.read() > 0 {",
-            bf::Token::LoopEnd(pos)   => "}",
-            bf::Token::ProgramEnd     => r#"
-                    Ok(())
-                }
-            "#
+    fn header() -> &'static str {
+        r#"
+            extern crate bf;
+
+            fn main() -> Result<(), std::io::Error> {
+                let mut state = bf::Buffer::<u32>::new(30000, bf::CellWrap::default());
+        "#
+    }
+
+    fn footer() -> &'static str {
+        r#"
+                Ok(())
+            }
+        "#
+    }
+
+    fn translate(expr: &bf::Expression) -> String {
+        match expr {
+            &bf::Expression::MoveForward(n) => format!("state.fwd({});", n),
+            &bf::Expression::MoveBack(n)    => format!("state.bwd({});", n),
+            &bf::Expression::IncValue(n)    => format!("state.inc({});", n),
+            &bf::Expression::DecValue(n)    => format!("state.dec({});", n),
+             bf::Expression::OutputValue    => "bf::print_mem(state.read())?;".to_string(),
+             bf::Expression::InputValue     => "state.write(bf::read_mem()?);".to_string(),
+            &bf::Expression::SetValue(n)    => format!("state.write({});", n),
+            &bf::Expression::AddMultiple { offset, factor } => format!(
+                "{{\n    let v = state.read();\n    state.move_by({0});\n    state.inc(v.wrapping_mul({1}));\n    state.move_by(-({0}));\n}}",
+                offset, factor
+            ),
+            &bf::Expression::Scan { step } =>
+                format!("while state.read() > 0 {{ state.move_by({}); }}", step),
+             bf::Expression::Loop(sub_exp) => format!(
+                 "while state.read() > 0 {{\n{}\n}}",
+                 translate_all::<RustTarget>(sub_exp)
+             ),
         }
     }
 }
@@ -37,55 +52,308 @@ impl Target for RustTarget {
 struct CTarget;
 
 impl Target for CTarget {
-    fn translate(token: &bf::Token) -> &'static str {
-        match token {
-            bf::Token::ProgramStart   => "
-                #include <stdio.h>
-                #include <stdlib.h>
-                int main() {
-                    char mem[30000],
-                    *ptr = mem;
-            ",
-            bf::Token::MoveForward(_) => "++ptr;",
-            bf::Token::MoveBack(_)    => "--ptr;",
-            bf::Token::IncValue(_)    => "++(*ptr);",
-            bf::Token::DecValue(_)    => "--(*ptr);",
-            bf::Token::OutputValue(_) => "putchar(*ptr);",
-            bf::Token::InputValue(_)  => "
+    fn header() -> &'static str {
+        "
+            #include <stdio.h>
+            #include <stdlib.h>
+            int main() {
+                char mem[30000],
+                *ptr = mem;
+        "
+    }
+
+    fn footer() -> &'static str {
+        "
+                return 0;
+            }
+        "
+    }
+
+    fn translate(expr: &bf::Expression) -> String {
+        match expr {
+            &bf::Expression::MoveForward(n) => format!("ptr += {};", n),
+            &bf::Expression::MoveBack(n)    => format!("ptr -= {};", n),
+            &bf::Expression::IncValue(n)    => format!("*ptr += {};", n),
+            &bf::Expression::DecValue(n)    => format!("*ptr -= {};", n),
+             bf::Expression::OutputValue    => "putchar(*ptr);".to_string(),
+             bf::Expression::InputValue     => "
                 *ptr = getchar();
                 if (*ptr == EOF) exit(0);
-            ",
-            bf::Token::LoopStart(_)   =>  "while(*ptr) {",
-            bf::Token::LoopEnd(pos)   => "}",
-            bf::Token::ProgramEnd     => "
-                    return 0;
-                }
-            "
+            ".to_string(),
+            &bf::Expression::SetValue(n)    => format!("*ptr = {};", n),
+            &bf::Expression::AddMultiple { offset, factor } =>
+                format!("ptr[{}] += *ptr * {};", offset, factor),
+            &bf::Expression::Scan { step } =>
+                format!("while (*ptr) ptr += {};", step),
+             bf::Expression::Loop(sub_exp) => format!(
+                 "while (*ptr) {{\n{}\n}}",
+                 translate_all::<CTarget>(sub_exp)
+             ),
         }
     }
 }
 
-fn translate<T: Target>(tokens: &Vec<bf::Token>) -> String {
+struct WatTarget;
+
+impl Target for WatTarget {
+    fn header() -> &'static str {
+        r#"
+            (module
+              (import "env" "read" (func $read (result i32)))
+              (import "env" "write" (func $write (param i32)))
+              (memory $mem 1)
+              (global $ptr (mut i32) (i32.const 0))
+              ;; Grows $mem a page at a time until it covers $addr, mirroring
+              ;; the interpreter's auto-growing tape (see `Buffer::fwd`).
+              (func $ensure_mem (param $addr i32)
+                (loop $grow
+                  local.get $addr
+                  memory.size
+                  i32.const 65536
+                  i32.mul
+                  i32.ge_u
+                  if
+                    i32.const 1
+                    memory.grow
+                    drop
+                    br $grow
+                  end
+                )
+              )
+              (func $main
+        "#
+    }
+
+    fn footer() -> &'static str {
+        r#"
+              )
+              (start $main)
+            )
+        "#
+    }
+
+    // Loops and scans branch by relative depth (`br_if 1` / `br 0`) rather
+    // than named labels, so nested loops need no label bookkeeping here:
+    // each one simply adds its own block/loop frame.
+    fn translate(expr: &bf::Expression) -> String {
+        match expr {
+            &bf::Expression::MoveForward(n) => format!(
+                "global.get $ptr\ni32.const {}\ni32.add\nglobal.set $ptr\nglobal.get $ptr\ncall $ensure_mem\n", n
+            ),
+            &bf::Expression::MoveBack(n) => format!(
+                "global.get $ptr\ni32.const {}\ni32.sub\nglobal.set $ptr\n", n
+            ),
+            &bf::Expression::IncValue(n) => format!(
+                "global.get $ptr\nglobal.get $ptr\ni32.load8_u\ni32.const {}\ni32.add\ni32.store8\n", n
+            ),
+            &bf::Expression::DecValue(n) => format!(
+                "global.get $ptr\nglobal.get $ptr\ni32.load8_u\ni32.const {}\ni32.sub\ni32.store8\n", n
+            ),
+             bf::Expression::OutputValue =>
+                "global.get $ptr\ni32.load8_u\ncall $write\n".to_string(),
+             bf::Expression::InputValue =>
+                "global.get $ptr\ncall $read\ni32.store8\n".to_string(),
+            &bf::Expression::SetValue(n) => format!(
+                "global.get $ptr\ni32.const {}\ni32.store8\n", n
+            ),
+            &bf::Expression::AddMultiple { offset, factor } => format!(
+                concat!(
+                    "global.get $ptr\ni32.const {0}\ni32.add\ncall $ensure_mem\n",
+                    "global.get $ptr\ni32.const {0}\ni32.add\n",
+                    "global.get $ptr\ni32.const {0}\ni32.add\ni32.load8_u\n",
+                    "global.get $ptr\ni32.load8_u\ni32.const {1}\ni32.mul\n",
+                    "i32.add\ni32.store8\n",
+                ),
+                offset, factor
+            ),
+            &bf::Expression::Scan { step } => format!(
+                concat!(
+                    "block\n",
+                    "loop\n",
+                    "global.get $ptr\ni32.load8_u\ni32.eqz\nbr_if 1\n",
+                    "global.get $ptr\ni32.const {0}\ni32.add\nglobal.set $ptr\n",
+                    "global.get $ptr\ncall $ensure_mem\n",
+                    "br 0\n",
+                    "end\n",
+                    "end\n",
+                ),
+                step
+            ),
+             bf::Expression::Loop(sub_exp) => format!(
+                concat!(
+                    "block\n",
+                    "loop\n",
+                    "global.get $ptr\ni32.load8_u\ni32.eqz\nbr_if 1\n",
+                    "{}",
+                    "br 0\n",
+                    "end\n",
+                    "end\n",
+                ),
+                translate_all::<WatTarget>(sub_exp)
+            ),
+        }
+    }
+}
+
+fn translate_all<T: Target>(expressions: &Vec<bf::Expression>) -> String {
     let mut program = String::new();
 
-    for token in tokens {
-        program.push_str(&T::translate(token))
+    for expr in expressions {
+        program.push_str(&T::translate(expr))
     }
 
     program
 }
 
+fn translate<T: Target>(expressions: &Vec<bf::Expression>) -> String {
+    let mut program = String::new();
+
+    program.push_str(T::header());
+    program.push_str(&translate_all::<T>(expressions));
+    program.push_str(T::footer());
+
+    program
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
+    let flag = |name| args.iter().any(|a| a == name);
 
     let mut bf_input = String::new();
     io::stdin().read_to_string(&mut bf_input).expect("Error reading stdin");
 
     let tokens = bf::tokenize(&bf_input.chars().collect());
 
+    if flag("--tokens") {
+        println!("{:#?}", tokens);
+        return;
+    }
+
+    let ast = match bf::parse(&tokens) {
+        Ok(ast) => ast,
+        Err(err) => {
+            eprintln!("Invalid brainfuck program: {:?}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if flag("--ast") {
+        println!("{:#?}", ast);
+        return;
+    }
+
+    let optimized = bf::optimize(&ast);
+
+    if flag("--optimized-ast") {
+        println!("{:#?}", optimized);
+        return;
+    }
+
+    if flag("--stats") {
+        println!("{:#?}", bf::stats(&optimized));
+        return;
+    }
+
+    if flag("--interpret") {
+        if let Err(err) = bf::run(&optimized) {
+            eprintln!("Runtime error: {:?}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     if args.len() < 2 || args[1] == "rs" {
-        print!("{}", translate::<RustTarget>(&tokens));
+        print!("{}", translate::<RustTarget>(&optimized));
+    } else if args[1] == "wat" {
+        print!("{}", translate::<WatTarget>(&optimized));
     } else {
-        print!("{}", translate::<CTarget>(&tokens));
+        print!("{}", translate::<CTarget>(&optimized));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate_wat(program: &str) -> String {
+        let tokens = bf::tokenize(&program.chars().collect());
+        let ast = bf::parse(&tokens).expect("valid program");
+        let optimized = bf::optimize(&ast);
+
+        translate::<WatTarget>(&optimized)
+    }
+
+    // Parses and runs the emitted module under a real wasm engine, the way
+    // `wat2wasm` + a host would, wiring up `env.read`/`env.write` to an
+    // in-memory input/output pair. Returns what the program wrote.
+    fn run_wat(wat: &str, input: &[u8]) -> Vec<u8> {
+        let binary = wat::parse_str(wat).expect("well-formed wat");
+
+        let engine = wasmi::Engine::default();
+        let module = wasmi::Module::new(&engine, &binary[..]).expect("type-checks");
+
+        struct Io {
+            input: std::vec::IntoIter<u8>,
+            output: Vec<u8>,
+        }
+
+        let mut store = wasmi::Store::new(&engine, Io { input: input.to_vec().into_iter(), output: Vec::new() });
+
+        let mut linker = wasmi::Linker::new(&engine);
+        linker.func_wrap("env", "read", |mut caller: wasmi::Caller<'_, Io>| -> i32 {
+            caller.data_mut().input.next().map(i32::from).unwrap_or(0)
+        }).unwrap();
+        linker.func_wrap("env", "write", |mut caller: wasmi::Caller<'_, Io>, byte: i32| {
+            caller.data_mut().output.push(byte as u8);
+        }).unwrap();
+
+        let instance = linker
+            .instantiate_and_start(&mut store, &module)
+            .expect("instantiates and runs without trapping");
+        let _ = instance;
+
+        store.into_data().output
+    }
+
+    #[test]
+    fn hello_world_wat_runs() {
+        let wat = translate_wat(
+            "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++."
+        );
+
+        let output = run_wat(&wat, &[]);
+
+        assert_eq!(output, b"Hello World!\n");
+    }
+
+    // `>` walks the pointer well past the single initial page; without
+    // `$ensure_mem` growing `$mem` to match the interpreter's auto-growing
+    // tape, the final store would trap on out-of-bounds memory access.
+    #[test]
+    fn move_forward_past_initial_page_does_not_trap() {
+        let mut program = ">".repeat(100_000);
+        program.push('+');
+        program.push('.');
+
+        let output = run_wat(&translate_wat(&program), &[]);
+
+        assert_eq!(output, vec![1]);
+    }
+
+    // The multiply-loop fold emits an `AddMultiple` whose offset can also
+    // reach past the initial page; its address computation must grow too.
+    #[test]
+    fn add_multiple_past_initial_page_does_not_trap() {
+        let mut program = "+++++[".to_string();
+        program.push_str(&">".repeat(70_000));
+        program.push_str("+++");
+        program.push_str(&"<".repeat(70_000));
+        program.push_str("-]");
+        program.push_str(&">".repeat(70_000));
+        program.push('.');
+
+        let output = run_wat(&translate_wat(&program), &[]);
+
+        assert_eq!(output, vec![15]);
     }
 }
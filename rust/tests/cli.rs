@@ -0,0 +1,62 @@
+//! Exercises the `translate` binary's CLI flags end to end, the way a user
+//! running `cargo run --bin translate` would invoke them.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run_translate(args: &[&str], stdin: &str) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_translate"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawns translate");
+
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().expect("runs to completion")
+}
+
+#[test]
+fn tokens_flag_prints_tokens() {
+    let output = run_translate(&["--tokens"], "+");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("IncValue"));
+}
+
+#[test]
+fn ast_flag_prints_unoptimized_ast() {
+    let output = run_translate(&["--ast"], "+++");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // Before `optimize` runs, each `+` is its own `IncValue` entry.
+    assert_eq!(stdout.matches("IncValue").count(), 3);
+}
+
+#[test]
+fn optimized_ast_flag_folds_clear_loop() {
+    let output = run_translate(&["--optimized-ast"], "+++[-]");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("SetValue"));
+    assert!(!stdout.contains("Loop"));
+}
+
+#[test]
+fn stats_flag_counts_instructions() {
+    let output = run_translate(&["--stats"], "++>+");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("inc_count"));
+    assert!(stdout.contains("fwd_count"));
+}
+
+#[test]
+fn interpret_flag_runs_the_program() {
+    let mut program = "+".repeat(65); // 'A'
+    program.push('.');
+
+    let output = run_translate(&["--interpret"], &program);
+
+    assert_eq!(output.stdout, b"A");
+}